@@ -4,14 +4,514 @@
   windows_subsystem = "windows"
 )]
 
+// The Isolation security pattern is opt-in, not baked into the default
+// config: `tauri.conf.json` leaves `tauri.security.pattern` at its default
+// ("brownfield"), and `tauri.isolation.conf.json` is a partial config that
+// overrides it to `"isolation"`, pointing at the isolation application in
+// `src-tauri/isolation`. Build or run with the override merged in to get
+// the hardened path, e.g. `tauri build --config src-tauri/tauri.isolation.conf.json`.
+// With it merged in, every `invoke()` call from the main webview is routed
+// through the isolation iframe's `__TAURI_ISOLATION_HOOK__` before it
+// reaches any `#[tauri::command]` registered below, so untrusted webview
+// content can't forge or tamper with IPC payloads without also
+// compromising the separate-origin isolation application. Without it,
+// the app builds and runs exactly as before this pattern existed.
+
+#[cfg(feature = "system-tray")]
+use tauri::{SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem};
+#[cfg(feature = "global-shortcut")]
+use tauri::GlobalShortcutManager;
+use tauri::{CustomMenuItem, Manager, Menu, MenuItem, Submenu, WindowMenuEvent};
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Runtime settings the frontend can read and mutate through the
+/// `get_settings` / `update_setting` / `reset_settings` commands.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Settings {
+  values: HashMap<String, String>,
+}
+
+/// Application state shared across all command invocations, registered
+/// with `Builder::manage` before `invoke_handler`.
+struct AppState {
+  settings: Mutex<Settings>,
+  /// Accelerator -> action name. Mirrors the on-disk store written by
+  /// `save_shortcuts` so lookups don't need to hit the filesystem.
+  #[cfg(feature = "global-shortcut")]
+  shortcuts: Mutex<HashMap<String, String>>,
+}
+
+impl AppState {
+  fn new() -> Self {
+    Self {
+      settings: Mutex::new(Settings::default()),
+      #[cfg(feature = "global-shortcut")]
+      shortcuts: Mutex::new(HashMap::new()),
+    }
+  }
+}
+
+#[cfg(feature = "global-shortcut")]
+const SHOW_WINDOW_SHORTCUT: &str = "CmdOrCtrl+Shift+D";
+#[cfg(feature = "global-shortcut")]
+const QUICK_ACTION_SHORTCUT: &str = "CmdOrCtrl+Shift+K";
+
 #[tauri::command]
 fn exit_app() {
   std::process::exit(0);
 }
 
+#[tauri::command]
+fn get_settings(state: tauri::State<'_, AppState>) -> Settings {
+  state.settings.lock().unwrap().clone()
+}
+
+/// Inserts/overwrites a single value and returns the resulting snapshot.
+/// Split out of the `update_setting` command so the transition itself is
+/// unit-testable without a live `tauri::State`.
+fn apply_update_setting(settings: &mut Settings, key: String, value: String) -> Settings {
+  settings.values.insert(key, value);
+  settings.clone()
+}
+
+#[tauri::command]
+fn update_setting(state: tauri::State<'_, AppState>, key: String, value: String) -> Settings {
+  apply_update_setting(&mut state.settings.lock().unwrap(), key, value)
+}
+
+/// Clears all values back to defaults and returns the resulting snapshot.
+/// Split out of the `reset_settings` command for the same reason as
+/// `apply_update_setting`.
+fn apply_reset_settings(settings: &mut Settings) -> Settings {
+  *settings = Settings::default();
+  settings.clone()
+}
+
+#[tauri::command]
+fn reset_settings(state: tauri::State<'_, AppState>) -> Settings {
+  apply_reset_settings(&mut state.settings.lock().unwrap())
+}
+
+#[cfg(test)]
+mod settings_tests {
+  use super::*;
+
+  #[test]
+  fn update_setting_inserts_and_overwrites_a_value() {
+    let mut settings = Settings::default();
+
+    let after_insert = apply_update_setting(&mut settings, "theme".into(), "dark".into());
+    assert_eq!(after_insert.values.get("theme"), Some(&"dark".to_string()));
+
+    let after_overwrite = apply_update_setting(&mut settings, "theme".into(), "light".into());
+    assert_eq!(after_overwrite.values.get("theme"), Some(&"light".to_string()));
+  }
+
+  #[test]
+  fn reset_settings_clears_every_value() {
+    let mut settings = Settings::default();
+    apply_update_setting(&mut settings, "theme".into(), "dark".into());
+
+    let after_reset = apply_reset_settings(&mut settings);
+
+    assert!(after_reset.values.is_empty());
+  }
+}
+
+/// A typed payload pushed to the frontend over the `backend-event` channel.
+/// Keeping this serde-serializable rather than passing raw strings lets the
+/// webview side deserialize into a matching TypeScript shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum BackendEvent {
+  Progress { percent: u8 },
+  Notification { message: String },
+}
+
+/// Example command showing the frontend can still explicitly ask the
+/// backend to emit, in addition to the unsolicited pushes from `setup`.
+#[tauri::command]
+fn subscribe(app_handle: tauri::AppHandle) {
+  let _ = app_handle.emit_all(
+    "backend-event",
+    BackendEvent::Notification {
+      message: "subscribed".into(),
+    },
+  );
+}
+
+/// Where bindings are persisted between runs, alongside the rest of Dragon
+/// Gate's app-specific config.
+#[cfg(feature = "global-shortcut")]
+fn shortcuts_store_path(app_handle: &tauri::AppHandle) -> Option<std::path::PathBuf> {
+  app_handle
+    .path_resolver()
+    .app_config_dir()
+    .map(|dir| dir.join("shortcuts.json"))
+}
+
+/// Reads the persisted binding map from `path`. Split out of `load_shortcuts`
+/// so the JSON round trip is unit-testable with a plain `Path`, no live
+/// `AppHandle` required.
+#[cfg(feature = "global-shortcut")]
+fn load_shortcuts_from(path: &std::path::Path) -> HashMap<String, String> {
+  std::fs::read_to_string(path)
+    .ok()
+    .and_then(|contents| serde_json::from_str(&contents).ok())
+    .unwrap_or_default()
+}
+
+/// Writes the binding map to `path`, creating its parent directory if
+/// needed. Split out of `save_shortcuts` for the same reason as
+/// `load_shortcuts_from`.
+#[cfg(feature = "global-shortcut")]
+fn save_shortcuts_to(path: &std::path::Path, shortcuts: &HashMap<String, String>) {
+  if let Some(dir) = path.parent() {
+    if std::fs::create_dir_all(dir).is_err() {
+      return;
+    }
+  }
+  if let Ok(contents) = serde_json::to_string(shortcuts) {
+    let _ = std::fs::write(path, contents);
+  }
+}
+
+#[cfg(feature = "global-shortcut")]
+fn load_shortcuts(app_handle: &tauri::AppHandle) -> HashMap<String, String> {
+  match shortcuts_store_path(app_handle) {
+    Some(path) => load_shortcuts_from(&path),
+    None => HashMap::new(),
+  }
+}
+
+#[cfg(feature = "global-shortcut")]
+fn save_shortcuts(app_handle: &tauri::AppHandle, shortcuts: &HashMap<String, String>) {
+  if let Some(path) = shortcuts_store_path(app_handle) {
+    save_shortcuts_to(&path, shortcuts);
+  }
+}
+
+/// Builds the handler a given binding invokes when the hotkey fires.
+/// `"show-window"` and `"quick-action"` are the two built-ins registered in
+/// `setup`; anything else is a custom binding the frontend asked for, which
+/// just gets forwarded as a `global-shortcut` event carrying its accelerator.
+#[cfg(feature = "global-shortcut")]
+fn make_shortcut_handler(
+  app_handle: tauri::AppHandle,
+  accelerator: String,
+  action: String,
+) -> impl Fn() + Send + 'static {
+  move || match action.as_str() {
+    "show-window" => {
+      if let Some(window) = app_handle.get_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+      }
+    }
+    "quick-action" => {
+      let _ = app_handle.emit_all("global-shortcut-quick-action", ());
+    }
+    _ => {
+      let _ = app_handle.emit_all("global-shortcut", &accelerator);
+    }
+  }
+}
+
+/// Adds/overwrites one binding in the map. Split out of `register_shortcut`
+/// so the bookkeeping it's responsible for is unit-testable without a live
+/// `AppHandle` (the OS-level `global_shortcut_manager().register()` call
+/// itself still needs one, and isn't something a unit test can exercise).
+#[cfg(feature = "global-shortcut")]
+fn remember_shortcut(shortcuts: &mut HashMap<String, String>, accelerator: String, action: String) {
+  shortcuts.insert(accelerator, action);
+}
+
+/// Removes one binding from the map. Split out of `unregister_shortcut` for
+/// the same reason as `remember_shortcut`.
+#[cfg(feature = "global-shortcut")]
+fn forget_shortcut(shortcuts: &mut HashMap<String, String>, accelerator: &str) {
+  shortcuts.remove(accelerator);
+}
+
+/// Registers a global (system-wide) hotkey and persists it to disk (and the
+/// in-memory `AppState` mirror) so it's restored on the next launch.
+/// `action` is an opaque name the frontend chooses; matching it back up to
+/// a command is the caller's job.
+#[cfg(feature = "global-shortcut")]
+#[tauri::command]
+fn register_shortcut(
+  app_handle: tauri::AppHandle,
+  state: tauri::State<'_, AppState>,
+  accelerator: String,
+  action: String,
+) -> Result<(), String> {
+  let handler = make_shortcut_handler(app_handle.clone(), accelerator.clone(), action.clone());
+  app_handle
+    .global_shortcut_manager()
+    .register(&accelerator, handler)
+    .map_err(|e| e.to_string())?;
+
+  let mut shortcuts = state.shortcuts.lock().unwrap();
+  remember_shortcut(&mut shortcuts, accelerator, action);
+  save_shortcuts(&app_handle, &shortcuts);
+  Ok(())
+}
+
+#[cfg(feature = "global-shortcut")]
+#[tauri::command]
+fn unregister_shortcut(
+  app_handle: tauri::AppHandle,
+  state: tauri::State<'_, AppState>,
+  accelerator: String,
+) -> Result<(), String> {
+  app_handle
+    .global_shortcut_manager()
+    .unregister(&accelerator)
+    .map_err(|e| e.to_string())?;
+
+  let mut shortcuts = state.shortcuts.lock().unwrap();
+  forget_shortcut(&mut shortcuts, &accelerator);
+  save_shortcuts(&app_handle, &shortcuts);
+  Ok(())
+}
+
+#[cfg(all(test, feature = "global-shortcut"))]
+mod shortcut_tests {
+  use super::*;
+
+  #[test]
+  fn register_then_unregister_round_trips_through_the_map() {
+    let mut shortcuts = HashMap::new();
+
+    remember_shortcut(
+      &mut shortcuts,
+      "CmdOrCtrl+Shift+D".to_string(),
+      "show-window".to_string(),
+    );
+    assert_eq!(
+      shortcuts.get("CmdOrCtrl+Shift+D"),
+      Some(&"show-window".to_string())
+    );
+
+    forget_shortcut(&mut shortcuts, "CmdOrCtrl+Shift+D");
+    assert!(shortcuts.is_empty());
+  }
+
+  #[test]
+  fn save_then_load_round_trips_through_disk() {
+    let dir = std::env::temp_dir().join(format!(
+      "dragon-gate-shortcut-test-{:?}",
+      std::thread::current().id()
+    ));
+    let path = dir.join("shortcuts.json");
+
+    let mut shortcuts = HashMap::new();
+    shortcuts.insert(SHOW_WINDOW_SHORTCUT.to_string(), "show-window".to_string());
+    shortcuts.insert(QUICK_ACTION_SHORTCUT.to_string(), "quick-action".to_string());
+
+    save_shortcuts_to(&path, &shortcuts);
+    let loaded = load_shortcuts_from(&path);
+
+    assert_eq!(loaded, shortcuts);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
+
+fn build_menu() -> Menu {
+  let file_menu = Submenu::new(
+    "File",
+    Menu::new()
+      .add_item(CustomMenuItem::new("new", "New").accelerator("CmdOrCtrl+N"))
+      .add_item(CustomMenuItem::new("open", "Open").accelerator("CmdOrCtrl+O"))
+      .add_native_item(MenuItem::Separator)
+      .add_item(CustomMenuItem::new("quit", "Quit").accelerator("CmdOrCtrl+Q")),
+  );
+
+  let edit_menu = Submenu::new(
+    "Edit",
+    Menu::new()
+      .add_item(CustomMenuItem::new("copy", "Copy").accelerator("CmdOrCtrl+C"))
+      .add_item(CustomMenuItem::new("paste", "Paste").accelerator("CmdOrCtrl+V")),
+  );
+
+  let mut view_menu_items = Menu::new();
+  #[cfg(debug_assertions)]
+  {
+    view_menu_items = view_menu_items.add_item(
+      CustomMenuItem::new("toggle_devtools", "Toggle DevTools").accelerator("CmdOrCtrl+Shift+I"),
+    );
+  }
+  view_menu_items =
+    view_menu_items.add_item(CustomMenuItem::new("reload", "Reload").accelerator("CmdOrCtrl+R"));
+  let view_menu = Submenu::new("View", view_menu_items);
+
+  Menu::new()
+    .add_submenu(file_menu)
+    .add_submenu(edit_menu)
+    .add_submenu(view_menu)
+}
+
+fn on_menu_event(event: WindowMenuEvent) {
+  let window = event.window();
+  match event.menu_item_id() {
+    "quit" => std::process::exit(0),
+    "toggle_devtools" => {
+      #[cfg(debug_assertions)]
+      {
+        if window.is_devtools_open() {
+          window.close_devtools();
+        } else {
+          window.open_devtools();
+        }
+      }
+    }
+    "reload" => {
+      let _ = window.emit("menu-reload", ());
+    }
+    // New/Open/Copy/Paste don't have a native Rust-side behavior; let the
+    // webview decide what to do with them.
+    id => {
+      let _ = window.emit("menu-event", id);
+    }
+  }
+}
+
+#[cfg(feature = "system-tray")]
+fn build_system_tray() -> SystemTray {
+  let menu = SystemTrayMenu::new()
+    .add_item(tauri::CustomMenuItem::new("show".to_string(), "Show"))
+    .add_item(tauri::CustomMenuItem::new("hide".to_string(), "Hide"))
+    .add_native_item(SystemTrayMenuItem::Separator)
+    .add_item(tauri::CustomMenuItem::new("quit".to_string(), "Quit"));
+
+  SystemTray::new().with_menu(menu)
+}
+
+#[cfg(feature = "system-tray")]
+fn on_system_tray_event(app: &tauri::AppHandle, event: SystemTrayEvent) {
+  match event {
+    SystemTrayEvent::LeftClick { .. } => {
+      let window = app.get_window("main").expect("main window not found");
+      if window.is_visible().unwrap_or(false) {
+        let _ = window.hide();
+      } else {
+        let _ = window.show();
+        let _ = window.set_focus();
+      }
+    }
+    SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
+      "show" => {
+        let window = app.get_window("main").expect("main window not found");
+        let _ = window.show();
+        let _ = window.set_focus();
+      }
+      "hide" => {
+        let window = app.get_window("main").expect("main window not found");
+        let _ = window.hide();
+      }
+      "quit" => std::process::exit(0),
+      _ => {}
+    },
+    _ => {}
+  }
+}
+
 fn main() {
-  tauri::Builder::default()
-    .invoke_handler(tauri::generate_handler![exit_app]) // Register the command
+  let builder = tauri::Builder::default();
+
+  #[cfg(feature = "system-tray")]
+  let builder = builder
+    .system_tray(build_system_tray())
+    .on_system_tray_event(on_system_tray_event)
+    .on_window_event(|event| {
+      if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
+        // Minimize to tray instead of quitting the whole app. Best-effort,
+        // like every other show/hide call in this file: a failure here
+        // shouldn't take the whole process down with it.
+        let _ = event.window().hide();
+        api.prevent_close();
+      }
+    });
+
+  #[cfg(feature = "global-shortcut")]
+  let invoke_handler = tauri::generate_handler![
+    exit_app,
+    get_settings,
+    update_setting,
+    reset_settings,
+    subscribe,
+    register_shortcut,
+    unregister_shortcut
+  ];
+  #[cfg(not(feature = "global-shortcut"))]
+  let invoke_handler = tauri::generate_handler![
+    exit_app,
+    get_settings,
+    update_setting,
+    reset_settings,
+    subscribe
+  ];
+
+  builder
+    .manage(AppState::new())
+    .menu(build_menu())
+    .on_menu_event(on_menu_event)
+    .setup(|app| {
+      let app_handle = app.handle();
+      let background_handle = app_handle.clone();
+      std::thread::spawn(move || {
+        // Stand-in for a real long-running task (e.g. a sync or download);
+        // ticks `percent` up to 100 and emits each step so the frontend has
+        // something concrete to render instead of a fixed placeholder.
+        let mut percent: u8 = 0;
+        loop {
+          std::thread::sleep(std::time::Duration::from_secs(5));
+          if background_handle
+            .emit_all("backend-event", BackendEvent::Progress { percent })
+            .is_err()
+          {
+            break;
+          }
+          percent = if percent >= 100 { 0 } else { percent + 10 };
+        }
+      });
+
+      // So Dragon Gate can be summoned or poked even when minimized/unfocused,
+      // since otherwise it's only listening for window-local shortcuts. A
+      // hotkey already held by another process, or a platform that doesn't
+      // support global shortcuts at all, shouldn't take the app down at
+      // launch, so failures here are logged and skipped rather than
+      // propagated with `?` (same best-effort policy as every other
+      // optional subsystem in this file).
+      #[cfg(feature = "global-shortcut")]
+      {
+        let mut shortcuts = load_shortcuts(&app_handle);
+        shortcuts
+          .entry(SHOW_WINDOW_SHORTCUT.to_string())
+          .or_insert_with(|| "show-window".to_string());
+        shortcuts
+          .entry(QUICK_ACTION_SHORTCUT.to_string())
+          .or_insert_with(|| "quick-action".to_string());
+
+        let mut manager = app_handle.global_shortcut_manager();
+        for (accelerator, action) in shortcuts.iter() {
+          let handler =
+            make_shortcut_handler(app_handle.clone(), accelerator.clone(), action.clone());
+          if let Err(err) = manager.register(accelerator, handler) {
+            eprintln!("dragon-gate: failed to register global shortcut {accelerator}: {err}");
+          }
+        }
+
+        save_shortcuts(&app_handle, &shortcuts);
+        *app.state::<AppState>().shortcuts.lock().unwrap() = shortcuts;
+      }
+
+      Ok(())
+    })
+    .invoke_handler(invoke_handler)
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }